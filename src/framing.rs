@@ -0,0 +1,83 @@
+// framing.rs
+//
+// The length-prefixed wire protocol and transport-agnostic stream trait
+// shared by `uciserver.rs` and `uciclient.rs`. Neither binary depends on the
+// other, so this file is pulled into both via `#[path = "framing.rs"] mod
+// framing;` rather than split into a separate lib target — one copy of the
+// source, compiled twice.
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+/// Channel id for the UCI stdin/stdout traffic in the framed wire protocol.
+pub const CHANNEL_UCI: u8 = 0;
+/// Channel id for the engine's stderr in the framed wire protocol.
+pub const CHANNEL_STDERR: u8 = 1;
+
+/// Largest payload `read_frame` will allocate for, regardless of what a
+/// peer's length header claims. UCI lines are tiny; this is generous
+/// headroom while still ruling out a multi-GB allocation (and the process
+/// abort that follows one) from a single forged header.
+pub const MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+/// Write one `[channel][len][payload]` record.
+pub fn write_frame<W: Write>(w: &mut W, channel: u8, payload: &[u8]) -> io::Result<()> {
+    w.write_all(&[channel])?;
+    w.write_all(&(payload.len() as u32).to_be_bytes())?;
+    w.write_all(payload)?;
+    w.flush()
+}
+
+/// Read one `[channel][len][payload]` record. Rejects a claimed length over
+/// `MAX_FRAME_LEN` instead of trusting it as an allocation size.
+pub fn read_frame<R: Read>(r: &mut R) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 5];
+    r.read_exact(&mut header)?;
+    let channel = header[0];
+    let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame length {} exceeds max of {}", len, MAX_FRAME_LEN),
+        ));
+    }
+    let mut payload = vec![0u8; len];
+    r.read_exact(&mut payload)?;
+    Ok((channel, payload))
+}
+
+/// A connection that can be split into independently-owned read/write
+/// halves, the way `TcpStream::try_clone`/`UnixStream::try_clone` do, plus a
+/// way to force a half that's blocked on a read to wake up. Lets the
+/// transport-agnostic pump functions in both binaries serve TCP and UDS
+/// alike; the `ws` transport has its own pump threads and does not go
+/// through this trait.
+pub trait DuplexStream: Read + Write + Send + 'static {
+    fn try_clone_duplex(&self) -> io::Result<Self>
+    where
+        Self: Sized;
+
+    fn shutdown_both(&self) -> io::Result<()>;
+}
+
+impl DuplexStream for TcpStream {
+    fn try_clone_duplex(&self) -> io::Result<Self> {
+        self.try_clone()
+    }
+
+    fn shutdown_both(&self) -> io::Result<()> {
+        self.shutdown(std::net::Shutdown::Both)
+    }
+}
+
+#[cfg(unix)]
+impl DuplexStream for UnixStream {
+    fn try_clone_duplex(&self) -> io::Result<Self> {
+        self.try_clone()
+    }
+
+    fn shutdown_both(&self) -> io::Result<()> {
+        self.shutdown(std::net::Shutdown::Both)
+    }
+}
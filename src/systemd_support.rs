@@ -0,0 +1,58 @@
+// systemd_support.rs
+//
+// sd-notify readiness/watchdog integration, gated behind the `systemd`
+// Cargo feature so it adds nothing to non-service deployments or non-Linux
+// builds. `uciserver.rs` calls `init` once, right after the listener bind
+// succeeds.
+use crate::ChildRegistry;
+use sd_notify::NotifyState;
+use signal_hook::consts::SIGTERM;
+use signal_hook::iterator::Signals;
+use std::thread;
+
+/// Tells the service manager we're up, starts the watchdog keepalive thread
+/// (if `WatchdogSec=` is set on the unit), and installs the SIGTERM handler
+/// that gracefully shuts down before exiting.
+pub fn init(children: ChildRegistry) {
+    if let Err(e) = sd_notify::notify(false, &[NotifyState::Ready]) {
+        eprintln!("sd_notify(READY) failed: {}", e);
+    }
+
+    spawn_watchdog();
+    spawn_sigterm_handler(children);
+}
+
+/// If the unit sets `WatchdogSec=`, systemd expects a `WATCHDOG=1` ping at
+/// less than that interval or it'll consider us hung and restart us; ping at
+/// half the interval for margin, per sd_notify(3)'s own recommendation.
+fn spawn_watchdog() {
+    if let Some(interval) = sd_notify::watchdog_enabled(false) {
+        let ping_every = interval / 2;
+        thread::spawn(move || loop {
+            thread::sleep(ping_every);
+            let _ = sd_notify::notify(false, &[NotifyState::Watchdog]);
+        });
+    }
+}
+
+/// On SIGTERM: tell the service manager we're stopping, kill/wait every
+/// engine still running, then exit. Letting the process exit is what stops
+/// us from accepting any further connections.
+fn spawn_sigterm_handler(children: ChildRegistry) {
+    let mut signals = Signals::new([SIGTERM]).expect("failed to register SIGTERM handler");
+    thread::spawn(move || {
+        for _ in signals.forever() {
+            eprintln!("Received SIGTERM, shutting down...");
+            let _ = sd_notify::notify(false, &[NotifyState::Stopping]);
+
+            for child in children.lock().unwrap().drain(..) {
+                if let Ok(mut child) = child.lock() {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+            }
+
+            std::process::exit(0);
+        }
+    });
+}
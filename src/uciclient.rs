@@ -1,18 +1,196 @@
 // client.rs
-use std::fs::{self, OpenOptions};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
 use std::io::{self, BufRead, BufReader, Read, Write};
 use std::net::{TcpStream, Shutdown};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::Duration;
 use serde::Deserialize;
 
+#[cfg(feature = "websocket")]
+use tungstenite::Message;
+
+#[path = "framing.rs"]
+mod framing;
+use framing::{read_frame, write_frame, CHANNEL_STDERR, CHANNEL_UCI, DuplexStream};
+
+type LogFile = Option<Arc<Mutex<File>>>;
+
 #[derive(Deserialize)]
 struct Config {
     server_address: String, // e.g., "192.168.1.100:6242"
     logfile: String,
     enable_logging: bool,
+    #[serde(default = "default_transport")]
+    transport: String, // "tcp" (default), "ws", or "unix", must match the server's transport
+    #[serde(default)]
+    framing: bool, // must match the server's `framing` setting
+    #[serde(default)]
+    reconnect: bool, // auto-reconnect (plain tcp transport only) when the connection drops
+    #[serde(default = "default_retry_delay_ms")]
+    retry_delay_ms: u64, // initial delay before the first reconnect attempt
+    #[serde(default = "default_max_backoff_ms")]
+    max_backoff_ms: u64, // cap on the exponential-backoff delay between attempts
+}
+
+fn default_transport() -> String {
+    "tcp".to_string()
+}
+
+fn default_retry_delay_ms() -> u64 {
+    1000
+}
+
+fn default_max_backoff_ms() -> u64 {
+    30_000
+}
+
+/// How many `setoption` lines are kept around to replay into a freshly
+/// (re)connected engine. `uci` and `ucinewgame` are tracked as standalone
+/// flags rather than FIFO entries (see `SessionBuffer`), so this only bounds
+/// the options, not the handful of other session-command kinds.
+const SESSION_BUFFER_CAPACITY: usize = 16;
+
+/// The commands that define the current analysis session — `uci`,
+/// `setoption ...`, `ucinewgame`, and the latest `position ...` — so a
+/// reconnect can replay them into the new engine and resume transparently.
+/// `uci` and `ucinewgame` are single flags rather than FIFO entries: a GUI
+/// that sends more than `capacity` `setoption` lines (plausible — engines
+/// routinely expose well over a dozen) must not be able to evict `uci` out
+/// of a shared ring buffer, since most engines expect it as the very first
+/// command.
+struct SessionBuffer {
+    capacity: usize,
+    uci: bool,
+    ucinewgame: bool,
+    position: Option<String>,
+    setoptions: VecDeque<String>,
+}
+
+impl SessionBuffer {
+    fn new(capacity: usize) -> Self {
+        SessionBuffer {
+            capacity,
+            uci: false,
+            ucinewgame: false,
+            position: None,
+            setoptions: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    fn push_line(&mut self, line: &str) {
+        let trimmed = line.trim();
+
+        if trimmed == "uci" {
+            self.uci = true;
+        } else if trimmed == "ucinewgame" {
+            self.ucinewgame = true;
+        } else if trimmed.starts_with("position") {
+            // Only the latest `position` matters; older ones are superseded.
+            self.position = Some(trimmed.to_string());
+        } else if trimmed.starts_with("setoption") {
+            if self.setoptions.len() >= self.capacity {
+                self.setoptions.pop_front();
+            }
+            self.setoptions.push_back(trimmed.to_string());
+        }
+    }
+
+    fn lines(&self) -> impl Iterator<Item = &str> {
+        let uci = if self.uci { Some("uci") } else { None };
+        let ucinewgame = if self.ucinewgame { Some("ucinewgame") } else { None };
+        // `setoption` must land before `ucinewgame`: engines read their
+        // options at `ucinewgame` time, so replaying the reverse order
+        // starts the new game with whatever options the engine defaulted to.
+        uci.into_iter()
+            .chain(self.setoptions.iter().map(String::as_str))
+            .chain(ucinewgame)
+            .chain(self.position.as_deref())
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.uci && !self.ucinewgame && self.position.is_none() && self.setoptions.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.uci as usize
+            + self.ucinewgame as usize
+            + self.position.is_some() as usize
+            + self.setoptions.len()
+    }
+}
+
+/// The write half of the current connection, plus any stdin lines that
+/// arrived while there wasn't one to write through (still connecting, or
+/// mid-reconnect while the session replay runs). Bundling both behind one
+/// lock closes the gap a separate "is there a stream yet" check would leave:
+/// a line can't be dropped between that check and queueing it.
+struct Outbound {
+    stream: Option<TcpStream>,
+    pending: VecDeque<Vec<u8>>,
+}
+
+impl Outbound {
+    fn new() -> Self {
+        Outbound {
+            stream: None,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+/// Send one line, queueing it instead of dropping it if there's no live
+/// connection to write through right now; `set_stream` flushes the queue
+/// once a connection is established.
+fn send_line(outbound: &Mutex<Outbound>, bytes: &[u8], framing: bool) {
+    let mut out = outbound.lock().unwrap();
+    match out.stream.as_mut() {
+        Some(stream) => {
+            let ok = if framing {
+                write_frame(stream, CHANNEL_UCI, bytes).is_ok()
+            } else {
+                stream.write_all(bytes).is_ok() && stream.flush().is_ok()
+            };
+            if !ok {
+                out.pending.push_back(bytes.to_vec());
+            }
+        }
+        None => out.pending.push_back(bytes.to_vec()),
+    }
+}
+
+/// Install the write half of a freshly (re)connected socket and flush any
+/// stdin lines that were queued while there wasn't one.
+fn set_stream(outbound: &Mutex<Outbound>, stream: TcpStream, framing: bool) {
+    let mut out = outbound.lock().unwrap();
+    out.stream = Some(stream);
+    while let Some(bytes) = out.pending.pop_front() {
+        if let Some(stream) = out.stream.as_mut() {
+            if framing {
+                let _ = write_frame(stream, CHANNEL_UCI, &bytes);
+            } else {
+                let _ = stream.write_all(&bytes);
+                let _ = stream.flush();
+            }
+        }
+    }
+}
+
+/// Sleep for `total_ms`, but re-check `shutdown` every `POLL_INTERVAL_MS` so
+/// a backoff delay (up to `max_backoff_ms`, 30s by default) can't hold the
+/// process open after the user's already typed "quit".
+fn interruptible_sleep(total_ms: u64, shutdown: &AtomicBool) {
+    const POLL_INTERVAL_MS: u64 = 100;
+    let mut remaining = total_ms;
+    while remaining > 0 && !shutdown.load(Ordering::SeqCst) {
+        let chunk = remaining.min(POLL_INTERVAL_MS);
+        thread::sleep(Duration::from_millis(chunk));
+        remaining = remaining.saturating_sub(chunk);
+    }
 }
 
 fn main() {
@@ -39,47 +217,309 @@ fn main() {
         None
     };
 
-    // Connect to server
+    match cfg.transport.as_str() {
+        "ws" => {
+            #[cfg(feature = "websocket")]
+            run_ws_client(&cfg, logfile);
+            #[cfg(not(feature = "websocket"))]
+            panic!("transport \"ws\" requires the websocket feature; rebuild with --features websocket");
+        }
+        "unix" => {
+            #[cfg(unix)]
+            run_unix_client(&cfg, logfile);
+            #[cfg(not(unix))]
+            panic!("transport \"unix\" is only supported on unix platforms");
+        }
+        _ => run_tcp_client(&cfg, logfile),
+    }
+}
+
+/// Reconnecting TCP client: wraps the connect + pump loop so a dropped or
+/// refused connection is retried (with exponential backoff) instead of
+/// ending the process, and replays the session-defining commands into the
+/// freshly (re)connected engine. Speaks the plain UCI byte stream or, when
+/// `cfg.framing` is set, the length-prefixed `[channel][len][payload]`
+/// protocol so the engine's stderr (channel 1) rides alongside its UCI
+/// traffic (channel 0) without corrupting either. Both variants share this
+/// loop since reconnect/backoff is oblivious to which bytes cross the wire.
+fn run_tcp_client(cfg: &Config, logfile: LogFile) {
+    let session = Arc::new(Mutex::new(SessionBuffer::new(SESSION_BUFFER_CAPACITY)));
+
+    // Shutdown is set once the user types "quit"; it's checked both by the
+    // per-connection stdout pump and by the reconnect loop below, and it
+    // outlives any single connection attempt.
+    let shutdown = Arc::new(AtomicBool::new(false));
+
+    // The stdin reader persists across reconnects; only the write half of
+    // the socket changes, so it writes through a handle that gets swapped
+    // in on every (re)connect. Lines typed while there's no live connection
+    // (still connecting, or mid-reconnect during session replay) queue in
+    // `Outbound::pending` instead of being silently dropped.
+    let outbound = Arc::new(Mutex::new(Outbound::new()));
+
+    let log_in = logfile.clone();
+    let shutdown_flag = Arc::clone(&shutdown);
+    let write_handle = Arc::clone(&outbound);
+    let session_in = Arc::clone(&session);
+    let framing = cfg.framing;
+    // Runs for the whole program lifetime, not just one connection attempt;
+    // deliberately left detached below instead of joined.
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        let reader = BufReader::new(stdin);
+
+        for line in reader.lines() {
+            match line {
+                Ok(line) => {
+                    let line_with_newline = format!("{}\n", line);
+                    let bytes = line_with_newline.as_bytes();
+
+                    send_line(&write_handle, bytes, framing);
+
+                    session_in.lock().unwrap().push_line(&line);
+
+                    // Log outgoing data (to server) if logging is enabled
+                    if let Some(ref log) = log_in {
+                        if let Ok(mut log) = log.lock() {
+                            let _ = log.write_all(b">> ");
+                            let _ = log.write_all(bytes);
+                            let _ = log.flush();
+                        }
+                    }
+
+                    // Check if the line is "quit" and exit
+                    if line.trim() == "quit" {
+                        eprintln!("Quit command received, disconnecting...");
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        shutdown_flag.store(true, Ordering::SeqCst);
+        // In framed mode `read_frame` can't be resumed mid-header across a
+        // read timeout the way the plain-byte pump can, so force the
+        // blocked stdout thread to wake up by shutting the connection down
+        // instead of relying on the timeout-and-flag trick below.
+        if framing {
+            if let Some(stream) = write_handle.lock().unwrap().stream.as_ref() {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+        }
+    });
+
+    let mut delay_ms = cfg.retry_delay_ms;
+
+    while !shutdown.load(Ordering::SeqCst) {
+        eprintln!("Connecting to server at {}...", cfg.server_address);
+        let stream = match TcpStream::connect(&cfg.server_address) {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("failed to connect to {}: {}", cfg.server_address, e);
+                if !cfg.reconnect {
+                    // Match the baseline `.expect(...)` behavior: a caller
+                    // that didn't opt into reconnect (monitoring scripts,
+                    // a systemd unit with Restart=on-failure, ...) needs a
+                    // non-zero exit to notice the connection never came up.
+                    std::process::exit(1);
+                }
+                eprintln!("Retrying in {}ms...", delay_ms);
+                interruptible_sleep(delay_ms, &shutdown);
+                delay_ms = (delay_ms * 2).min(cfg.max_backoff_ms);
+                continue;
+            }
+        };
+
+        // The plain-byte pump polls `shutdown` on a read timeout; the framed
+        // pump instead gets unstuck via the stdin thread's
+        // `shutdown(Shutdown::Both)` above, so it can block indefinitely on `read_frame`.
+        if !framing {
+            stream
+                .set_read_timeout(Some(Duration::from_millis(100)))
+                .expect("failed to set read timeout");
+        }
+
+        eprintln!("Connected to server at {}", cfg.server_address);
+        if cfg.enable_logging {
+            eprintln!("Logging enabled: {}", cfg.logfile);
+        }
+        delay_ms = cfg.retry_delay_ms; // a successful connect resets the backoff
+
+        // Replay the session-defining commands from before the disconnect so
+        // analysis can resume transparently against the freshly spawned engine.
+        {
+            let session = session.lock().unwrap();
+            if !session.is_empty() {
+                let mut replay_stream = stream.try_clone().expect("failed to clone stream");
+                for line in session.lines() {
+                    let bytes = format!("{}\n", line);
+                    if framing {
+                        let _ = write_frame(&mut replay_stream, CHANNEL_UCI, bytes.as_bytes());
+                    } else {
+                        let _ = replay_stream.write_all(bytes.as_bytes());
+                        let _ = replay_stream.flush();
+                    }
+                }
+                eprintln!("Replayed {} session command(s)", session.len());
+            }
+        }
+
+        set_stream(
+            &outbound,
+            stream.try_clone().expect("failed to clone stream"),
+            framing,
+        );
+        let mut read_stream = stream.try_clone().expect("failed to clone stream");
+        let shutdown_stream = stream;
+
+        // Thread: network -> stdout, for this connection attempt only
+        let log_out = logfile.clone();
+        let shutdown_check = Arc::clone(&shutdown);
+        let stdout_thread = thread::spawn(move || {
+            let mut stdout = io::stdout();
+            if framing {
+                loop {
+                    match read_frame(&mut read_stream) {
+                        Ok((CHANNEL_UCI, payload)) => {
+                            let _ = stdout.write_all(&payload);
+                            let _ = stdout.flush();
+
+                            if let Some(ref log) = log_out {
+                                if let Ok(mut log) = log.lock() {
+                                    let _ = log.write_all(b"<< ");
+                                    let _ = log.write_all(&payload);
+                                    let _ = log.flush();
+                                }
+                            }
+                        }
+                        Ok((CHANNEL_STDERR, payload)) => {
+                            eprint!("(engine) {}", String::from_utf8_lossy(&payload));
+                        }
+                        Ok(_) => continue,
+                        Err(_) => break,
+                    }
+                }
+                return;
+            }
+
+            let mut buf = [0u8; 4096];
+            loop {
+                // Check if we should shutdown
+                if shutdown_check.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                match read_stream.read(&mut buf) {
+                    Ok(0) => break, // Connection closed
+                    Ok(n) => {
+                        let _ = stdout.write_all(&buf[..n]);
+                        let _ = stdout.flush();
+
+                        // Log incoming data (from server) if logging is enabled
+                        if let Some(ref log) = log_out {
+                            if let Ok(mut log) = log.lock() {
+                                let _ = log.write_all(b"<< ");
+                                let _ = log.write_all(&buf[..n]);
+                                let _ = log.flush();
+                            }
+                        }
+                    }
+                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock
+                               || e.kind() == io::ErrorKind::TimedOut => {
+                        // Timeout, check shutdown flag again
+                        continue;
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let _ = stdout_thread.join();
+        outbound.lock().unwrap().stream = None;
+        let _ = shutdown_stream.shutdown(Shutdown::Both);
+
+        if shutdown.load(Ordering::SeqCst) || !cfg.reconnect {
+            break;
+        }
+
+        eprintln!("Connection lost, reconnecting in {}ms...", delay_ms);
+        interruptible_sleep(delay_ms, &shutdown);
+        delay_ms = (delay_ms * 2).min(cfg.max_backoff_ms);
+    }
+
+    eprintln!("Disconnected from server");
+}
+
+/// Decode `server_address` and connect over a Unix domain socket. A leading
+/// `\0name` connects to a name in the abstract namespace (Linux only), mirroring
+/// `uciserver.rs`'s `bind_unix_listener`; anything else is a filesystem path.
+#[cfg(unix)]
+fn connect_unix_stream(server_address: &str) -> io::Result<UnixStream> {
+    if let Some(name) = server_address.strip_prefix("\\0") {
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::linux::net::SocketAddrExt;
+            use std::os::unix::net::SocketAddr;
+            let addr = SocketAddr::from_abstract_name(name.as_bytes())?;
+            return UnixStream::connect_addr(&addr);
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = name;
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "abstract unix sockets are only supported on Linux",
+            ));
+        }
+    }
+    UnixStream::connect(server_address)
+}
+
+/// Unix-domain-socket counterpart of `run_tcp_client`, used when `transport`
+/// is "unix"; `server_address` then names a local socket path (or abstract
+/// name) instead of a host:port. Reconnect/backoff is scoped to the TCP transport (see
+/// `Config::reconnect`), so this speaks whichever of the two pumps `framing`
+/// selects and returns once the connection closes.
+#[cfg(unix)]
+fn run_unix_client(cfg: &Config, logfile: LogFile) {
     eprintln!("Connecting to server at {}...", cfg.server_address);
-    let stream = TcpStream::connect(&cfg.server_address)
+    let stream = connect_unix_stream(&cfg.server_address)
         .expect(&format!("failed to connect to {}", cfg.server_address));
-    
-    // Set read timeout to allow periodic checking for shutdown
-    stream.set_read_timeout(Some(Duration::from_millis(100)))
-        .expect("failed to set read timeout");
-    
+
     eprintln!("Connected to server at {}", cfg.server_address);
     if cfg.enable_logging {
         eprintln!("Logging enabled: {}", cfg.logfile);
     }
 
-    // Shutdown flag shared between threads
-    let shutdown = Arc::new(AtomicBool::new(false));
+    if cfg.framing {
+        pump_client_framed(stream, cfg, logfile);
+    } else {
+        pump_client(stream, cfg, logfile);
+    }
+}
 
-    // Clone the stream for bidirectional communication
-    let mut read_stream = stream.try_clone().expect("failed to clone stream");
-    let mut write_stream = stream.try_clone().expect("failed to clone stream");
-    let shutdown_stream = stream.try_clone().expect("failed to clone stream");
+/// Transport-agnostic two-thread pump (stdin -> socket, socket -> stdout)
+/// shared by the non-reconnecting, non-framed clients.
+fn pump_client<S: DuplexStream>(stream: S, _cfg: &Config, logfile: LogFile) {
+    let mut read_stream = stream.try_clone_duplex().expect("failed to clone stream");
+    let mut write_stream = stream.try_clone_duplex().expect("failed to clone stream");
 
-    // Thread: stdin -> network
     let log_in = logfile.clone();
-    let shutdown_flag = Arc::clone(&shutdown);
     let stdin_thread = thread::spawn(move || {
         let stdin = io::stdin();
         let reader = BufReader::new(stdin);
-        
+
         for line in reader.lines() {
             match line {
                 Ok(line) => {
                     let line_with_newline = format!("{}\n", line);
                     let bytes = line_with_newline.as_bytes();
-                    
+
                     if write_stream.write_all(bytes).is_err() {
                         break;
                     }
                     let _ = write_stream.flush();
-                    
-                    // Log outgoing data (to server) if logging is enabled
+
                     if let Some(ref log) = log_in {
                         if let Ok(mut log) = log.lock() {
                             let _ = log.write_all(b">> ");
@@ -87,11 +527,9 @@ fn main() {
                             let _ = log.flush();
                         }
                     }
-                    
-                    // Check if the line is "quit" and exit
+
                     if line.trim() == "quit" {
                         eprintln!("Quit command received, disconnecting...");
-                        shutdown_flag.store(true, Ordering::SeqCst);
                         break;
                     }
                 }
@@ -100,25 +538,17 @@ fn main() {
         }
     });
 
-    // Thread: network -> stdout
     let log_out = logfile.clone();
-    let shutdown_check = Arc::clone(&shutdown);
     let stdout_thread = thread::spawn(move || {
         let mut stdout = io::stdout();
         let mut buf = [0u8; 4096];
         loop {
-            // Check if we should shutdown
-            if shutdown_check.load(Ordering::SeqCst) {
-                break;
-            }
-            
             match read_stream.read(&mut buf) {
-                Ok(0) => break, // Connection closed
+                Ok(0) => break,
                 Ok(n) => {
                     let _ = stdout.write_all(&buf[..n]);
                     let _ = stdout.flush();
-                    
-                    // Log incoming data (from server) if logging is enabled
+
                     if let Some(ref log) = log_out {
                         if let Ok(mut log) = log.lock() {
                             let _ = log.write_all(b"<< ");
@@ -127,11 +557,197 @@ fn main() {
                         }
                     }
                 }
-                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock 
-                           || e.kind() == io::ErrorKind::TimedOut => {
-                    // Timeout, check shutdown flag again
-                    continue;
+                Err(_) => break,
+            }
+        }
+    });
+
+    let _ = stdin_thread.join();
+    let _ = stdout_thread.join();
+
+    eprintln!("Disconnected from server");
+}
+
+/// Transport-agnostic version of the framed (channel-multiplexed) pump; used
+/// by `run_unix_client` (the TCP transport's framed mode now goes through
+/// `run_tcp_client`'s reconnect loop instead).
+fn pump_client_framed<S: DuplexStream>(stream: S, _cfg: &Config, logfile: LogFile) {
+    let mut read_stream = stream.try_clone_duplex().expect("failed to clone stream");
+    let mut write_stream = stream.try_clone_duplex().expect("failed to clone stream");
+    let shutdown_stream = stream;
+
+    // Thread: stdin -> network, framed on channel 0
+    let log_in = logfile.clone();
+    let stdin_thread = thread::spawn(move || {
+        let stdin = io::stdin();
+        let reader = BufReader::new(stdin);
+
+        for line in reader.lines() {
+            match line {
+                Ok(line) => {
+                    let line_with_newline = format!("{}\n", line);
+                    let bytes = line_with_newline.as_bytes();
+
+                    if write_frame(&mut write_stream, CHANNEL_UCI, bytes).is_err() {
+                        break;
+                    }
+
+                    if let Some(ref log) = log_in {
+                        if let Ok(mut log) = log.lock() {
+                            let _ = log.write_all(b">> ");
+                            let _ = log.write_all(bytes);
+                            let _ = log.flush();
+                        }
+                    }
+
+                    if line.trim() == "quit" {
+                        eprintln!("Quit command received, disconnecting...");
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        // Nothing left to send; force the blocked reader thread to wake up
+        // instead of relying on the timeout-and-flag trick `run_tcp_client`
+        // uses, since `read_frame` can't be resumed mid-header across a
+        // read timeout.
+        let _ = write_stream.shutdown_both();
+    });
+
+    // Thread: network -> stdout / stderr, demultiplexed by channel
+    let log_out = logfile.clone();
+    let stdout_thread = thread::spawn(move || {
+        let mut stdout = io::stdout();
+        loop {
+            match read_frame(&mut read_stream) {
+                Ok((CHANNEL_UCI, payload)) => {
+                    let _ = stdout.write_all(&payload);
+                    let _ = stdout.flush();
+
+                    if let Some(ref log) = log_out {
+                        if let Ok(mut log) = log.lock() {
+                            let _ = log.write_all(b"<< ");
+                            let _ = log.write_all(&payload);
+                            let _ = log.flush();
+                        }
+                    }
+                }
+                Ok((CHANNEL_STDERR, payload)) => {
+                    eprint!("(engine) {}", String::from_utf8_lossy(&payload));
+                }
+                Ok(_) => continue,
+                Err(_) => break,
+            }
+        }
+    });
+
+    let _ = stdin_thread.join();
+    let _ = stdout_thread.join();
+
+    let _ = shutdown_stream.shutdown_both();
+
+    eprintln!("Disconnected from server");
+}
+
+/// WebSocket counterpart of `run_tcp_client`, used to exercise the server's
+/// `transport = "ws"` mode without a browser.
+#[cfg(feature = "websocket")]
+fn run_ws_client(cfg: &Config, logfile: LogFile) {
+    let url = format!("ws://{}", cfg.server_address);
+    eprintln!("Connecting to server at {}...", url);
+    let (ws, _response) =
+        tungstenite::connect(&url).expect(&format!("failed to connect to {}", url));
+    eprintln!("Connected to server at {}", cfg.server_address);
+    if cfg.enable_logging {
+        eprintln!("Logging enabled: {}", cfg.logfile);
+    }
+
+    // A single WebSocket shared behind one Mutex would let the reader thread
+    // hold the lock for as long as it's blocked waiting on server output,
+    // starving the writer thread of the lock it needs to deliver a queued
+    // keystroke (e.g. `stop`) in the meantime. The handshake is already done,
+    // so instead split the underlying stream the same way the raw-TCP path
+    // does and give each direction its own `WebSocket`, with no lock between
+    // them.
+    let read_stream = ws.get_ref().try_clone().expect("failed to clone stream");
+    let write_stream = ws.into_inner();
+    let mut ws_reader =
+        tungstenite::WebSocket::from_raw_socket(read_stream, tungstenite::protocol::Role::Client, None);
+    let mut ws_writer = tungstenite::WebSocket::from_raw_socket(
+        write_stream,
+        tungstenite::protocol::Role::Client,
+        None,
+    );
+    let shutdown = Arc::new(AtomicBool::new(false));
+
+    let log_in = logfile.clone();
+    let shutdown_flag = Arc::clone(&shutdown);
+    let stdin_thread = thread::spawn(move || {
+        let stdin = io::stdin();
+        let reader = BufReader::new(stdin);
+
+        for line in reader.lines() {
+            match line {
+                Ok(line) => {
+                    let line_with_newline = format!("{}\n", line);
+
+                    if ws_writer
+                        .write_message(Message::Text(line_with_newline.clone()))
+                        .is_err()
+                    {
+                        break;
+                    }
+
+                    if let Some(ref log) = log_in {
+                        if let Ok(mut log) = log.lock() {
+                            let _ = log.write_all(b">> ");
+                            let _ = log.write_all(line_with_newline.as_bytes());
+                            let _ = log.flush();
+                        }
+                    }
+
+                    if line.trim() == "quit" {
+                        eprintln!("Quit command received, disconnecting...");
+                        shutdown_flag.store(true, Ordering::SeqCst);
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        let _ = ws_writer.close(None);
+    });
+
+    let log_out = logfile.clone();
+    let shutdown_check = Arc::clone(&shutdown);
+    let stdout_thread = thread::spawn(move || {
+        let mut stdout = io::stdout();
+        loop {
+            if shutdown_check.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let msg = ws_reader.read_message();
+            match msg {
+                Ok(Message::Text(text)) => {
+                    let _ = stdout.write_all(text.as_bytes());
+                    let _ = stdout.flush();
+
+                    if let Some(ref log) = log_out {
+                        if let Ok(mut log) = log.lock() {
+                            let _ = log.write_all(b"<< ");
+                            let _ = log.write_all(text.as_bytes());
+                            let _ = log.flush();
+                        }
+                    }
+                }
+                Ok(Message::Binary(data)) => {
+                    let _ = stdout.write_all(&data);
+                    let _ = stdout.flush();
                 }
+                Ok(Message::Close(_)) => break,
+                Ok(_) => continue,
                 Err(_) => break,
             }
         }
@@ -139,10 +755,7 @@ fn main() {
 
     let _ = stdin_thread.join();
     let _ = stdout_thread.join();
-    
-    // Shutdown the connection
-    let _ = shutdown_stream.shutdown(Shutdown::Both);
-    
+
     eprintln!("Disconnected from server");
 }
 
@@ -2,14 +2,48 @@
 use std::fs;
 use std::io::{Read, Write};
 use std::net::{TcpListener, TcpStream};
-use std::process::{Command, Stdio};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use serde::Deserialize;
 
+#[cfg(feature = "websocket")]
+use tungstenite::Message;
+
+#[cfg(feature = "systemd")]
+mod systemd_support;
+
+#[path = "framing.rs"]
+mod framing;
+use framing::{read_frame, write_frame, CHANNEL_STDERR, CHANNEL_UCI, DuplexStream};
+
 #[derive(Deserialize)]
 struct Config {
     engine: String,
     bind_address: String, // e.g., "0.0.0.0:6242" to listen on all interfaces
+    #[serde(default = "default_max_clients")]
+    max_clients: usize, // cap on concurrently running engine instances
+    #[serde(default = "default_transport")]
+    transport: String, // "tcp" (default), "ws", or "unix"
+    #[serde(default)]
+    framing: bool, // length-prefixed [channel][len][payload] records so stderr can ride the same stream
+    #[serde(default = "default_detect_external_ip")]
+    detect_external_ip: bool, // set false to skip the lookup on air-gapped deployments
+}
+
+fn default_max_clients() -> usize {
+    8
+}
+
+fn default_transport() -> String {
+    "tcp".to_string()
+}
+
+fn default_detect_external_ip() -> bool {
+    true
 }
 
 fn main() {
@@ -22,29 +56,95 @@ fn main() {
         .expect(&format!("failed to read {}", config_file));
     let cfg: Config = serde_json::from_str(&cfg_data)
         .expect("failed to parse config");
+    let cfg = Arc::new(cfg);
 
     // Get external IP address
-    println!("Detecting external IP address...");
-    match get_external_ip() {
-        Ok(ip) => println!("External IP: {}", ip),
-        Err(e) => eprintln!("Failed to get external IP: {}", e),
+    if cfg.detect_external_ip {
+        println!("Detecting external IP address...");
+        match get_external_ip() {
+            Ok(ip) => println!("External IP: {}", ip),
+            Err(e) => eprintln!("Failed to get external IP: {}", e),
+        }
     }
 
-    // Bind to TCP port
+    println!("Max concurrent clients: {}", cfg.max_clients);
+
+    if cfg.transport == "ws" {
+        #[cfg(not(feature = "websocket"))]
+        panic!("transport \"ws\" requires the websocket feature; rebuild with --features websocket");
+    }
+
+    // Each connection gets its own thread (and its own spawned engine), so a
+    // slow or long-lived session never blocks anyone else from connecting.
+    let active_clients = Arc::new(AtomicUsize::new(0));
+
+    // Tracks every spawned engine so a `systemd` SIGTERM shutdown (see
+    // `systemd_support`) can kill/wait them even while their connections are
+    // still being served on other threads.
+    let children: ChildRegistry = Arc::new(Mutex::new(Vec::new()));
+
+    if cfg.transport == "unix" {
+        #[cfg(unix)]
+        run_unix_listener(cfg, active_clients, children);
+        #[cfg(not(unix))]
+        panic!("transport \"unix\" is only supported on Unix platforms");
+    } else {
+        run_tcp_listener(cfg, active_clients, children);
+    }
+}
+
+fn run_tcp_listener(cfg: Arc<Config>, active_clients: Arc<AtomicUsize>, children: ChildRegistry) {
     let listener = TcpListener::bind(&cfg.bind_address)
         .expect(&format!("failed to bind to {}", cfg.bind_address));
-    
-    println!("Server listening on {}", cfg.bind_address);
+
+    println!("Server listening on {} ({})", cfg.bind_address, cfg.transport);
     println!("Clients should connect to: <external_ip>:6242");
     println!("Waiting for connections...");
 
-    // Accept connections (one at a time for now)
+    #[cfg(feature = "systemd")]
+    systemd_support::init(Arc::clone(&children));
+
     for stream in listener.incoming() {
         match stream {
             Ok(stream) => {
-                println!("Client connected: {}", stream.peer_addr().unwrap());
-                handle_client(stream, &cfg);
-                println!("Client disconnected");
+                let cfg = Arc::clone(&cfg);
+                let active_clients = Arc::clone(&active_clients);
+                let children = Arc::clone(&children);
+
+                thread::spawn(move || {
+                    // A connection that's already reset (common with
+                    // health-check probes/port scanners) can fail here even
+                    // though accept() succeeded; that must not take down the
+                    // listener, so it's handled per-thread instead of with a
+                    // top-level `.unwrap()` in the accept loop.
+                    let peer = match stream.peer_addr() {
+                        Ok(peer) => peer.to_string(),
+                        Err(e) => {
+                            eprintln!("Client connected but peer_addr() failed: {}", e);
+                            "unknown".to_string()
+                        }
+                    };
+                    println!("Client connected: {}", peer);
+
+                    if active_clients.fetch_add(1, Ordering::SeqCst) >= cfg.max_clients {
+                        active_clients.fetch_sub(1, Ordering::SeqCst);
+                        reject_busy(stream);
+                        println!("Client rejected (server busy): {}", peer);
+                        return;
+                    }
+
+                    match cfg.transport.as_str() {
+                        "ws" => {
+                            #[cfg(feature = "websocket")]
+                            handle_ws_client(stream, &cfg, &children);
+                            #[cfg(not(feature = "websocket"))]
+                            eprintln!("dropping {}: websocket feature not compiled in", peer);
+                        }
+                        _ => handle_client(stream, &cfg, &children),
+                    }
+                    active_clients.fetch_sub(1, Ordering::SeqCst);
+                    println!("Client disconnected: {}", peer);
+                });
             }
             Err(e) => {
                 eprintln!("Connection failed: {}", e);
@@ -53,6 +153,89 @@ fn main() {
     }
 }
 
+/// Accepts connections on a Unix-domain socket, either on the filesystem at
+/// `bind_address` or, on Linux, in the abstract namespace when `bind_address`
+/// starts with the escaped-NUL marker `\0name`. Everything past accept() is
+/// identical to the TCP path: one thread per connection, same engine pool cap.
+#[cfg(unix)]
+fn run_unix_listener(cfg: Arc<Config>, active_clients: Arc<AtomicUsize>, children: ChildRegistry) {
+    let listener = bind_unix_listener(&cfg.bind_address)
+        .expect(&format!("failed to bind to {}", cfg.bind_address));
+
+    println!("Server listening on {} ({})", cfg.bind_address, cfg.transport);
+    println!("Waiting for connections...");
+
+    #[cfg(feature = "systemd")]
+    systemd_support::init(Arc::clone(&children));
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                println!("Client connected");
+
+                let cfg = Arc::clone(&cfg);
+                let active_clients = Arc::clone(&active_clients);
+                let children = Arc::clone(&children);
+
+                thread::spawn(move || {
+                    if active_clients.fetch_add(1, Ordering::SeqCst) >= cfg.max_clients {
+                        active_clients.fetch_sub(1, Ordering::SeqCst);
+                        reject_busy(stream);
+                        println!("Client rejected (server busy)");
+                        return;
+                    }
+
+                    handle_unix_client(stream, &cfg, &children);
+                    active_clients.fetch_sub(1, Ordering::SeqCst);
+                    println!("Client disconnected");
+                });
+            }
+            Err(e) => {
+                eprintln!("Connection failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Binds `bind_address` as a Unix-domain socket. A leading `\0` marks an
+/// abstract-namespace name (Linux only, per the usual `\0name` convention:
+/// no backing file, reclaimed automatically when every reference closes);
+/// anything else is a filesystem path, which is unlinked first in case a
+/// previous run left the socket file behind.
+#[cfg(unix)]
+fn bind_unix_listener(bind_address: &str) -> std::io::Result<UnixListener> {
+    if let Some(name) = bind_address.strip_prefix("\\0") {
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::linux::net::SocketAddrExt;
+            use std::os::unix::net::SocketAddr;
+            let addr = SocketAddr::from_abstract_name(name.as_bytes())?;
+            return UnixListener::bind_addr(&addr);
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = name;
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "abstract unix sockets are only supported on Linux",
+            ));
+        }
+    }
+
+    let _ = fs::remove_file(bind_address); // drop a stale socket file from a previous run
+    UnixListener::bind(bind_address)
+}
+
+/// Turn away a connection once `max_clients` engines are already running,
+/// rather than silently queuing it behind an unbounded pile of spawned engines.
+fn reject_busy<W: Write>(mut stream: W) {
+    let _ = stream.write_all(b"info string server busy\n");
+    let _ = stream.flush();
+}
+
+/// Timeout for a single external-IP lookup request.
+const IP_LOOKUP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
 fn get_external_ip() -> Result<String, Box<dyn std::error::Error>> {
     // Try multiple services in case one is down
     let services = [
@@ -72,56 +255,39 @@ fn get_external_ip() -> Result<String, Box<dyn std::error::Error>> {
     Err("All IP lookup services failed".into())
 }
 
+/// Fetches `url` with an in-process HTTP client instead of shelling out to
+/// curl/wget/PowerShell, so it works on minimal containers that lack those
+/// binaries and enforces IPv4 and the timeout consistently across platforms.
 fn try_ip_service(url: &str) -> Result<String, Box<dyn std::error::Error>> {
-    // Try using curl first (most likely to be available)
-    if let Ok(output) = Command::new("curl")
-        .arg("-s")
-        .arg("-4") // Force IPv4
-        .arg("--max-time")
-        .arg("5")
-        .arg(url)
-        .output()
-    {
-        if output.status.success() {
-            return Ok(String::from_utf8_lossy(&output.stdout).to_string());
-        }
-    }
-
-    // Try wget as fallback
-    if let Ok(output) = Command::new("wget")
-        .arg("-qO-")
-        .arg("--timeout=5")
-        .arg(url)
-        .output()
-    {
-        if output.status.success() {
-            return Ok(String::from_utf8_lossy(&output.stdout).to_string());
-        }
-    }
-
-    // Try PowerShell on Windows
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(output) = Command::new("powershell")
-            .arg("-Command")
-            .arg(format!("(Invoke-WebRequest -Uri {} -UseBasicParsing -TimeoutSec 5).Content", url))
-            .output()
-        {
-            if output.status.success() {
-                return Ok(String::from_utf8_lossy(&output.stdout).to_string());
-            }
-        }
-    }
+    let client = reqwest::blocking::Client::builder()
+        .timeout(IP_LOOKUP_TIMEOUT)
+        .local_address(std::net::IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED)) // force IPv4
+        .build()?;
 
-    Err("Failed to fetch IP".into())
+    let body = client.get(url).send()?.error_for_status()?.text()?;
+    Ok(body)
 }
 
-fn handle_client(stream: TcpStream, cfg: &Config) {
-    // Spawn engine with platform-specific settings
+/// Shared handle to a spawned engine process. Held by both the pump that's
+/// feeding/draining it and, when the `systemd` feature is enabled, the
+/// SIGTERM shutdown path in `systemd_support`, so either side can kill it.
+pub(crate) type ChildGuard = Arc<Mutex<Child>>;
+/// Every engine currently spawned, across all transports and connections.
+pub(crate) type ChildRegistry = Arc<Mutex<Vec<ChildGuard>>>;
+
+/// Spawn the configured engine with the platform-specific settings shared by
+/// every transport (TCP, WS, ...). `capture_stderr` pipes the engine's stderr
+/// instead of discarding it, for transports that can carry it separately.
+/// The child is registered in `children` so it can be reached for a
+/// graceful shutdown; callers must `deregister_child` once they've killed it.
+fn spawn_engine(cfg: &Config, capture_stderr: bool, children: &ChildRegistry) -> ChildGuard {
     let mut cmd = Command::new(&cfg.engine);
-    cmd.stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::null());
+    cmd.stdin(Stdio::piped()).stdout(Stdio::piped());
+    cmd.stderr(if capture_stderr {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    });
 
     // Windows-specific: hide console window
     #[cfg(target_os = "windows")]
@@ -131,13 +297,46 @@ fn handle_client(stream: TcpStream, cfg: &Config) {
         cmd.creation_flags(CREATE_NO_WINDOW);
     }
 
-    let mut child = cmd.spawn().expect("failed to spawn engine");
+    let child = Arc::new(Mutex::new(cmd.spawn().expect("failed to spawn engine")));
+    children.lock().unwrap().push(Arc::clone(&child));
+    child
+}
+
+/// Remove a child previously registered by `spawn_engine`, once its pump has
+/// killed/waited it. Leaving it registered would let the shutdown path in
+/// `systemd_support` try to kill an already-reaped process.
+fn deregister_child(children: &ChildRegistry, child: &ChildGuard) {
+    children.lock().unwrap().retain(|c| !Arc::ptr_eq(c, child));
+}
+
+fn handle_client(stream: TcpStream, cfg: &Config, children: &ChildRegistry) {
+    if cfg.framing {
+        pump_engine_framed(stream, cfg, children);
+    } else {
+        pump_engine(stream, cfg, children);
+    }
+}
+
+#[cfg(unix)]
+fn handle_unix_client(stream: UnixStream, cfg: &Config, children: &ChildRegistry) {
+    if cfg.framing {
+        pump_engine_framed(stream, cfg, children);
+    } else {
+        pump_engine(stream, cfg, children);
+    }
+}
+
+/// Bridges any `DuplexStream` (raw TCP or Unix-domain socket) to an engine:
+/// one thread pumps network bytes into the engine's stdin, another pumps the
+/// engine's stdout back out.
+fn pump_engine<S: DuplexStream>(stream: S, cfg: &Config, children: &ChildRegistry) {
+    let child = spawn_engine(cfg, false, children);
 
-    let mut engine_stdin = child.stdin.take().expect("engine stdin");
-    let mut engine_stdout = child.stdout.take().expect("engine stdout");
+    let mut engine_stdin = child.lock().unwrap().stdin.take().expect("engine stdin");
+    let mut engine_stdout = child.lock().unwrap().stdout.take().expect("engine stdout");
 
     // Clone the stream for bidirectional communication
-    let mut read_stream = stream.try_clone().expect("failed to clone stream");
+    let mut read_stream = stream.try_clone_duplex().expect("failed to clone stream");
     let mut write_stream = stream;
 
     // Thread: network -> engine stdin
@@ -174,8 +373,172 @@ fn handle_client(stream: TcpStream, cfg: &Config) {
 
     let _ = stdin_thread.join();
     let _ = stdout_thread.join();
-    let _ = child.kill(); // Ensure engine is terminated
-    let _ = child.wait();
+    {
+        let mut child = child.lock().unwrap();
+        let _ = child.kill(); // Ensure engine is terminated
+        let _ = child.wait();
+    }
+    deregister_child(children, &child);
+}
+
+/// Same bridging as `pump_engine`, but wire traffic is length-prefixed
+/// `[channel][len][payload]` records so the engine's stderr (channel 1) can
+/// share the stream with its stdin/stdout UCI traffic (channel 0) without
+/// corrupting either stream.
+fn pump_engine_framed<S: DuplexStream>(stream: S, cfg: &Config, children: &ChildRegistry) {
+    let child = spawn_engine(cfg, true, children);
+
+    let mut engine_stdin = child.lock().unwrap().stdin.take().expect("engine stdin");
+    let mut engine_stdout = child.lock().unwrap().stdout.take().expect("engine stdout");
+    let mut engine_stderr = child.lock().unwrap().stderr.take().expect("engine stderr");
+
+    let mut read_stream = stream.try_clone_duplex().expect("failed to clone stream");
+    // Both the stdout and stderr pumps write frames onto the same stream, so
+    // they share one handle behind a mutex to keep frames from interleaving.
+    let write_stream = Arc::new(Mutex::new(stream));
+
+    // Thread: network -> engine stdin (channel 0 only; other channels are ignored)
+    let stdin_thread = thread::spawn(move || loop {
+        match read_frame(&mut read_stream) {
+            Ok((CHANNEL_UCI, payload)) => {
+                if engine_stdin.write_all(&payload).is_err() {
+                    break;
+                }
+                let _ = engine_stdin.flush();
+            }
+            Ok(_) => continue,
+            Err(_) => break,
+        }
+    });
+
+    // Thread: engine stdout -> network, framed on channel 0
+    let write_stdout = Arc::clone(&write_stream);
+    let stdout_thread = thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = match engine_stdout.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(_) => break,
+            };
+            let mut w = write_stdout.lock().unwrap();
+            if write_frame(&mut *w, CHANNEL_UCI, &buf[..n]).is_err() {
+                break;
+            }
+        }
+    });
+
+    // Thread: engine stderr -> network, framed on channel 1
+    let write_stderr = Arc::clone(&write_stream);
+    let stderr_thread = thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = match engine_stderr.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(_) => break,
+            };
+            let mut w = write_stderr.lock().unwrap();
+            if write_frame(&mut *w, CHANNEL_STDERR, &buf[..n]).is_err() {
+                break;
+            }
+        }
+    });
+
+    let _ = stdin_thread.join();
+    let _ = stdout_thread.join();
+    let _ = stderr_thread.join();
+    {
+        let mut child = child.lock().unwrap();
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    deregister_child(children, &child);
+}
+
+/// Bridges a WebSocket connection to an engine the same way `handle_client`
+/// bridges a raw TCP stream: one thread pumps inbound frames into the
+/// engine's stdin, another wraps engine stdout chunks into outbound frames.
+#[cfg(feature = "websocket")]
+fn handle_ws_client(stream: TcpStream, cfg: &Config, children: &ChildRegistry) {
+    let ws = match tungstenite::accept(stream) {
+        Ok(ws) => ws,
+        Err(e) => {
+            eprintln!("WebSocket handshake failed: {}", e);
+            return;
+        }
+    };
+    // A single WebSocket shared behind one Mutex would let the reader thread
+    // hold the lock for as long as it's blocked waiting on the next client
+    // message, starving the writer thread of the lock it needs to deliver
+    // engine output (e.g. `bestmove` after `go infinite`) in the meantime.
+    // The handshake is already done, so instead split the underlying stream
+    // the same way the raw-TCP path does and give each direction its own
+    // `WebSocket`, with no lock between them.
+    let read_stream = ws.get_ref().try_clone().expect("failed to clone stream");
+    let write_stream = ws.into_inner();
+    let mut ws_reader = tungstenite::WebSocket::from_raw_socket(
+        read_stream,
+        tungstenite::protocol::Role::Server,
+        None,
+    );
+    let mut ws_writer = tungstenite::WebSocket::from_raw_socket(
+        write_stream,
+        tungstenite::protocol::Role::Server,
+        None,
+    );
+
+    let child = spawn_engine(cfg, false, children);
+
+    let mut engine_stdin = child.lock().unwrap().stdin.take().expect("engine stdin");
+    let mut engine_stdout = child.lock().unwrap().stdout.take().expect("engine stdout");
+
+    // Thread: WS -> engine stdin
+    let stdin_thread = thread::spawn(move || loop {
+        let msg = ws_reader.read_message();
+        match msg {
+            Ok(Message::Text(text)) => {
+                if engine_stdin.write_all(text.as_bytes()).is_err() {
+                    break;
+                }
+                let _ = engine_stdin.flush();
+            }
+            Ok(Message::Binary(data)) => {
+                if engine_stdin.write_all(&data).is_err() {
+                    break;
+                }
+                let _ = engine_stdin.flush();
+            }
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue, // ping/pong/frame are handled by tungstenite itself
+            Err(_) => break,
+        }
+    });
+
+    // Thread: engine stdout -> WS
+    let stdout_thread = thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = match engine_stdout.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(_) => break,
+            };
+            let chunk = String::from_utf8_lossy(&buf[..n]).into_owned();
+            if ws_writer.write_message(Message::Text(chunk)).is_err() {
+                break;
+            }
+        }
+    });
+
+    let _ = stdin_thread.join();
+    let _ = stdout_thread.join();
+    {
+        let mut child = child.lock().unwrap();
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    deregister_child(children, &child);
 }
 
 fn parse_config_arg(args: &[String]) -> Option<String> {